@@ -1,7 +1,7 @@
 use clap::Parser as ClapParser;
 use std::{
     fs::{self},
-    io,
+    io::{self, Read},
     path::PathBuf,
 };
 
@@ -22,71 +22,304 @@ enum Token {
     Eof,
 }
 
+/// `Token` 去掉承载数据后的种类标签，用于栈式校验中比较与报告"期望的 token"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    LeftBrace,
+    RightBrace,
+    String,
+    Number,
+    True,
+    False,
+    Null,
+    LeftBracket,
+    RightBracket,
+    Colon,
+    Comma,
+    Eof,
+}
+
+impl TokenKind {
+    fn label(&self) -> &'static str {
+        match self {
+            TokenKind::LeftBrace => "{",
+            TokenKind::RightBrace => "}",
+            TokenKind::String => "string",
+            TokenKind::Number => "number",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::Null => "null",
+            TokenKind::LeftBracket => "[",
+            TokenKind::RightBracket => "]",
+            TokenKind::Colon => ":",
+            TokenKind::Comma => ",",
+            TokenKind::Eof => "end of input",
+        }
+    }
+}
+
+impl Token {
+    fn kind(&self) -> TokenKind {
+        match self {
+            Token::LeftBrace => TokenKind::LeftBrace,
+            Token::RightBrace => TokenKind::RightBrace,
+            Token::String(_) => TokenKind::String,
+            Token::Number(_) => TokenKind::Number,
+            Token::True => TokenKind::True,
+            Token::False => TokenKind::False,
+            Token::Null => TokenKind::Null,
+            Token::LeftBracket => TokenKind::LeftBracket,
+            Token::RightBracket => TokenKind::RightBracket,
+            Token::Colon => TokenKind::Colon,
+            Token::Comma => TokenKind::Comma,
+            Token::Whitespace => unreachable!("whitespace tokens are filtered before parsing"),
+            Token::Eof => TokenKind::Eof,
+        }
+    }
+}
+
+fn format_kinds(kinds: &[TokenKind]) -> String {
+    let labels: Vec<String> = kinds.iter().map(|k| format!("{:?}", k.label())).collect();
+    format!("[{}]", labels.join(", "))
+}
+
 #[derive(Debug)]
 struct Lexer {
     input: Vec<char>,
     pos: usize,
+    line: usize,
+    col: usize,
+    // 宽松模式下接受 JSON5 风格的 `//`、`/* */` 注释
+    lenient: bool,
 }
 
 impl Lexer {
-    fn new(input: String) -> Self {
+    fn new(input: String, lenient: bool) -> Self {
         Self {
             input: input.chars().collect(),
             pos: 0,
+            line: 1,
+            col: 1,
+            lenient,
+        }
+    }
+
+    /// 消费当前字符并推进 line/col：遇到换行则换行号 +1、列号归 1，否则列号 +1
+    fn advance(&mut self) -> char {
+        let ch = self.input[self.pos];
+        self.pos += 1;
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
         }
+        ch
     }
 
     /// 当前位置是空白字符则跳过
     fn skio_whitespace(&mut self) {
         while self.pos < self.input.len() && self.input[self.pos].is_whitespace() {
-            self.pos += 1
+            self.advance();
         }
     }
 
-    fn next_token(&mut self) -> Token {
-        self.skio_whitespace();
+    /// 返回 token 及其起始位置 (line, col)
+    fn next_token(&mut self) -> Result<(Token, usize, usize), String> {
+        loop {
+            self.skio_whitespace();
+
+            let line = self.line;
+            let col = self.col;
 
-        if self.pos >= self.input.len() {
-            return Token::Eof;
+            if self.pos >= self.input.len() {
+                return Ok((Token::Eof, line, col));
+            }
+
+            if self.lenient && self.input[self.pos] == '/' {
+                self.skip_comment(line, col)?;
+                continue;
+            }
+
+            return self.read_token(line, col).map(|token| (token, line, col));
         }
+    }
 
-        let ch = self.input[self.pos];
-        self.pos += 1;
+    /// 消费并跳过一条 `//` 行注释或 `/* */` 块注释（已确认 lenient 为真且当前字符为 `/`）
+    fn skip_comment(&mut self, line: usize, col: usize) -> Result<(), String> {
+        self.advance(); // 消费第一个 '/'
+        match self.input.get(self.pos) {
+            Some('/') => {
+                self.advance();
+                while self.pos < self.input.len() && self.input[self.pos] != '\n' {
+                    self.advance();
+                }
+                Ok(())
+            }
+            Some('*') => {
+                self.advance();
+                loop {
+                    if self.pos + 1 >= self.input.len() {
+                        return Err(format!(
+                            "Unterminated block comment starting at line {line}, column {col}"
+                        ));
+                    }
+                    if self.input[self.pos] == '*' && self.input[self.pos + 1] == '/' {
+                        self.advance();
+                        self.advance();
+                        return Ok(());
+                    }
+                    self.advance();
+                }
+            }
+            _ => Err(format!(
+                "Unexpected character '/' at line {line}, column {col}"
+            )),
+        }
+    }
 
-        match ch {
+    fn read_token(&mut self, line: usize, col: usize) -> Result<Token, String> {
+        let ch = self.advance();
+
+        let token = match ch {
             '{' => Token::LeftBrace,
             '}' => Token::RightBrace,
             '[' => Token::LeftBracket,
             ']' => Token::RightBracket,
             ':' => Token::Colon,
             ',' => Token::Comma,
-            '"' => self.read_string(),
+            '"' => self.read_string(line, col)?,
             't' => self.read_true(),
             'f' => self.read_false(),
             'n' => self.read_null(),
-            '0'..='9' | '-' => self.read_number(ch),
+            '0'..='9' | '-' => self.read_number(ch, line, col)?,
+            '/' => {
+                return Err(format!(
+                    "Unexpected character '/' at line {line}, column {col}"
+                ))
+            }
             _ => Token::Whitespace,
-        }
+        };
+
+        Ok(token)
     }
 
-    fn read_string(&mut self) -> Token {
+    /// 读取并解析一个带转义序列的字符串字面量，`start_line`/`start_col` 用于报告未闭合字符串的位置
+    fn read_string(&mut self, start_line: usize, start_col: usize) -> Result<Token, String> {
         let mut result = String::new();
-        while self.pos < self.input.len() && self.input[self.pos] != '"' {
-            result.push(self.input[self.pos]);
-            self.pos += 1;
+
+        loop {
+            if self.pos >= self.input.len() {
+                return Err(format!(
+                    "Unterminated string literal starting at line {start_line}, column {start_col}"
+                ));
+            }
+
+            match self.input[self.pos] {
+                '"' => {
+                    self.advance();
+                    break;
+                }
+                '\\' => {
+                    let line = self.line;
+                    let col = self.col;
+                    self.advance(); // 消费反斜杠
+                    result.push(self.read_escape(line, col)?);
+                }
+                ch if (ch as u32) < 0x20 => {
+                    return Err(format!(
+                        "Control character in string literal at line {}, column {}",
+                        self.line, self.col
+                    ));
+                }
+                _ => result.push(self.advance()),
+            }
         }
-        if self.pos < self.input.len() && self.input[self.pos] == '"' {
-            self.pos += 1;
+
+        Ok(Token::String(result))
+    }
+
+    /// 读取反斜杠之后的转义序列并返回对应字符
+    fn read_escape(&mut self, line: usize, col: usize) -> Result<char, String> {
+        if self.pos >= self.input.len() {
+            return Err(format!(
+                "Unterminated escape sequence at line {line}, column {col}"
+            ));
+        }
+
+        let escape = self.advance();
+        match escape {
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            '/' => Ok('/'),
+            'b' => Ok('\u{0008}'),
+            'f' => Ok('\u{000C}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            'u' => self.read_unicode_escape(line, col),
+            other => Err(format!(
+                "Invalid escape sequence '\\{other}' at line {line}, column {col}"
+            )),
+        }
+    }
+
+    /// 读取 `\uXXXX`，并在遇到高代理项时继续读取紧随其后的低代理项以组合成一个码点
+    fn read_unicode_escape(&mut self, line: usize, col: usize) -> Result<char, String> {
+        let high = self.read_hex4(line, col)?;
+
+        if (0xD800..=0xDBFF).contains(&high) {
+            if self.pos + 1 >= self.input.len()
+                || self.input[self.pos] != '\\'
+                || self.input[self.pos + 1] != 'u'
+            {
+                return Err(format!(
+                    "Unpaired surrogate \\u{high:04x} at line {line}, column {col}"
+                ));
+            }
+            self.advance(); // 反斜杠
+            self.advance(); // u
+            let low = self.read_hex4(line, col)?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return Err(format!(
+                    "Invalid low surrogate \\u{low:04x} at line {line}, column {col}"
+                ));
+            }
+            let code = 0x10000 + (high - 0xD800) * 0x400 + (low - 0xDC00);
+            char::from_u32(code)
+                .ok_or_else(|| format!("Invalid surrogate pair at line {line}, column {col}"))
+        } else {
+            char::from_u32(high).ok_or_else(|| {
+                format!("Invalid unicode escape \\u{high:04x} at line {line}, column {col}")
+            })
         }
+    }
 
-        Token::String(result)
+    /// 读取 4 位十六进制数字
+    fn read_hex4(&mut self, line: usize, col: usize) -> Result<u32, String> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            if self.pos >= self.input.len() {
+                return Err(format!(
+                    "Unexpected end of input in \\u escape at line {line}, column {col}"
+                ));
+            }
+            let digit = self.advance();
+            let digit_value = digit.to_digit(16).ok_or_else(|| {
+                format!("Invalid hex digit '{digit}' in \\u escape at line {line}, column {col}")
+            })?;
+            value = value * 16 + digit_value;
+        }
+        Ok(value)
     }
 
     fn read_true(&mut self) -> Token {
         if self.pos + 3 <= self.input.len()
             && self.input[self.pos - 1..self.pos + 3] == ['t', 'r', 'u', 'e']
         {
-            self.pos += 3;
+            for _ in 0..3 {
+                self.advance();
+            }
             Token::True
         } else {
             Token::Whitespace
@@ -97,7 +330,9 @@ impl Lexer {
         if self.pos + 4 <= self.input.len()
             && self.input[self.pos - 1..self.pos + 4] == ['f', 'a', 'l', 's', 'e']
         {
-            self.pos += 4;
+            for _ in 0..4 {
+                self.advance();
+            }
             Token::False
         } else {
             Token::Whitespace
@@ -108,22 +343,86 @@ impl Lexer {
         if self.pos + 3 <= self.input.len()
             && self.input[self.pos - 1..self.pos + 3] == ['n', 'u', 'l', 'l']
         {
-            self.pos += 3;
+            for _ in 0..3 {
+                self.advance();
+            }
             Token::Null
         } else {
             Token::Whitespace
         }
     }
 
-    fn read_number(&mut self, first: char) -> Token {
-        let mut number = first.to_string();
-        while self.pos < self.input.len()
-            && (self.input[self.pos].is_digit(10) || self.input[self.pos] == '.')
+    /// 按 JSON 数字语法解析：可选的 `-`，整数部分（单独的 `0` 或 `1-9` 后跟数字，禁止前导零），
+    /// 可选的小数部分，可选的指数部分
+    fn read_number(&mut self, first: char, line: usize, col: usize) -> Result<Token, String> {
+        let mut number = String::new();
+        let mut current = first;
+
+        if current == '-' {
+            number.push(current);
+            if self.pos >= self.input.len() || !self.input[self.pos].is_ascii_digit() {
+                return Err(format!(
+                    "Expected digit after '-' in number at line {line}, column {col}"
+                ));
+            }
+            current = self.advance();
+        }
+
+        number.push(current);
+        if current == '0' {
+            if self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+                return Err(format!(
+                    "Leading zeros are not allowed in number at line {line}, column {col}"
+                ));
+            }
+        } else {
+            while self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+                number.push(self.advance());
+            }
+        }
+
+        if self.pos < self.input.len() && self.input[self.pos] == '.' {
+            number.push(self.advance());
+            if self.pos >= self.input.len() || !self.input[self.pos].is_ascii_digit() {
+                return Err(format!(
+                    "Expected digit after decimal point at line {line}, column {col}"
+                ));
+            }
+            while self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+                number.push(self.advance());
+            }
+        }
+
+        if self.pos < self.input.len()
+            && (self.input[self.pos] == 'e' || self.input[self.pos] == 'E')
         {
-            number.push(self.input[self.pos]);
-            self.pos += 1;
+            number.push(self.advance());
+            if self.pos < self.input.len()
+                && (self.input[self.pos] == '+' || self.input[self.pos] == '-')
+            {
+                number.push(self.advance());
+            }
+            if self.pos >= self.input.len() || !self.input[self.pos].is_ascii_digit() {
+                return Err(format!(
+                    "Expected digit in exponent at line {line}, column {col}"
+                ));
+            }
+            while self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+                number.push(self.advance());
+            }
         }
-        Token::Number(number.parse().unwrap_or(0.0))
+
+        let parsed: f64 = number
+            .parse()
+            .map_err(|e| format!("Invalid number '{number}' at line {line}, column {col}: {e}"))?;
+
+        if !parsed.is_finite() {
+            return Err(format!(
+                "Number '{number}' is out of range at line {line}, column {col}"
+            ));
+        }
+
+        Ok(Token::Number(parsed))
     }
 }
 
@@ -139,32 +438,69 @@ enum JsonValue {
 
 #[derive(Debug)]
 struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<(Token, usize, usize)>,
     pos: usize,
+    // 输入结尾的位置，用于到达末尾时仍能报告一个有意义的位置
+    eof_line: usize,
+    eof_col: usize,
+    // 宽松模式下允许对象/数组在 `}`、`]` 前出现多余的逗号
+    lenient: bool,
 }
 
 impl Parser {
-    fn new(tokens: Vec<Token>) -> Self {
+    fn new(
+        tokens: Vec<(Token, usize, usize)>,
+        eof_line: usize,
+        eof_col: usize,
+        lenient: bool,
+    ) -> Self {
         Self {
-            tokens: tokens,
+            tokens,
             pos: 0,
+            eof_line,
+            eof_col,
+            lenient,
+        }
+    }
+
+    /// 当前 token 的位置，若已到达末尾则使用输入结尾的位置
+    fn current_pos(&self) -> (usize, usize) {
+        match self.tokens.get(self.pos) {
+            Some((_, line, col)) => (*line, *col),
+            None => (self.eof_line, self.eof_col),
         }
     }
 
+    fn error_at(&self, message: &str) -> String {
+        let (line, col) = self.current_pos();
+        format!("{message} at line {line}, column {col}")
+    }
+
+    /// 解析整份输入，并确认顶层值之后没有残留的 token（例如 `1.2.3`、`true false`）
+    fn parse_document(&mut self) -> Result<JsonValue, String> {
+        let value = self.parse()?;
+        if self.pos != self.tokens.len() {
+            return Err(self.error_at("Unexpected trailing data"));
+        }
+        Ok(value)
+    }
+
     fn parse(&mut self) -> Result<JsonValue, String> {
         if self.pos >= self.tokens.len() {
-            return Err("Unexpected end of input".into());
+            return Err(self.error_at("Unexpected end of input"));
         }
-        match &self.tokens[self.pos] {
+        match &self.tokens[self.pos].0 {
             Token::LeftBrace => self.parse_object(),
             Token::LeftBracket => self.parse_array(),
             Token::String(s) => {
+                let s = s.clone();
                 self.pos += 1;
-                Ok(JsonValue::String(s.clone()))
+                Ok(JsonValue::String(s))
             }
             Token::Number(n) => {
+                let n = *n;
                 self.pos += 1;
-                Ok(JsonValue::Number(*n))
+                Ok(JsonValue::Number(n))
             }
             Token::True => {
                 self.pos += 1;
@@ -179,8 +515,8 @@ impl Parser {
                 Ok(JsonValue::Null)
             }
             e => {
-                println!("Error: {:?}", e);
-                Err("Invalid JSON structure".to_string())
+                let message = format!("Invalid JSON structure, found {:?}", e);
+                Err(self.error_at(&message))
             }
         }
     }
@@ -189,26 +525,26 @@ impl Parser {
         self.pos += 1; // 跳过左 { 字符
         let mut pairs = Vec::new();
 
-        if self.pos < self.tokens.len() && self.tokens[self.pos] == Token::RightBrace {
+        if self.pos < self.tokens.len() && self.tokens[self.pos].0 == Token::RightBrace {
             self.pos += 1;
             return Ok(JsonValue::Object(pairs));
         }
 
         loop {
             if self.pos >= self.tokens.len() {
-                return Err("Unclosed object".to_string()); // 最后一个字符了还是对象解析，则是没有关闭对象
+                return Err(self.error_at("Unclosed object")); // 最后一个字符了还是对象解析，则是没有关闭对象
             }
 
             // 在 JSON 中 key 必须是一个 String 类型
-            let key = match &self.tokens[self.pos] {
+            let key = match &self.tokens[self.pos].0 {
                 Token::String(s) => s.clone(),
-                _ => return Err("Expected string key".to_string()),
+                _ => return Err(self.error_at("Expected string key")),
             };
             self.pos += 1;
 
             // key 之后接着应该是一个 : 符号
-            if self.pos >= self.tokens.len() || self.tokens[self.pos] != Token::Colon {
-                return Err("Expected colon after key".to_string());
+            if self.pos >= self.tokens.len() || self.tokens[self.pos].0 != Token::Colon {
+                return Err(self.error_at("Expected colon after key"));
             }
             self.pos += 1;
 
@@ -219,20 +555,28 @@ impl Parser {
 
             // 解析出值以后到达末尾，则是未关闭的 JSON
             if self.pos >= self.tokens.len() {
-                return Err("Unclosed object".to_string());
+                return Err(self.error_at("Unclosed object"));
             }
 
             // value的下一个字符串必须是：} 或 ,
-            match self.tokens[self.pos] {
+            match self.tokens[self.pos].0 {
                 Token::RightBrace => {
                     self.pos += 1;
                     break;
                 }
                 Token::Comma => {
                     self.pos += 1;
+                    // 宽松模式下允许逗号后紧跟闭合括号（尾逗号）
+                    if self.lenient
+                        && self.pos < self.tokens.len()
+                        && self.tokens[self.pos].0 == Token::RightBrace
+                    {
+                        self.pos += 1;
+                        break;
+                    }
                     continue;
                 }
-                _ => return Err("Expected commna or closing brace".to_string()),
+                _ => return Err(self.error_at("Expected commna or closing brace")),
             }
         }
 
@@ -243,75 +587,537 @@ impl Parser {
         self.pos += 1;
         let mut elements = Vec::new();
 
-        if self.pos < self.tokens.len() && self.tokens[self.pos] == Token::RightBracket {
+        if self.pos < self.tokens.len() && self.tokens[self.pos].0 == Token::RightBracket {
             self.pos += 1;
             return Ok(JsonValue::Array(elements));
         }
 
         loop {
             if self.pos >= self.tokens.len() {
-                return Err("Unclosed array".to_string());
+                return Err(self.error_at("Unclosed array"));
             }
 
             let value = self.parse()?;
             elements.push(value);
 
             if self.pos >= self.tokens.len() {
-                return Err("Unclosed array".to_string());
+                return Err(self.error_at("Unclosed array"));
             }
 
-            match self.tokens[self.pos] {
+            match self.tokens[self.pos].0 {
                 Token::RightBracket => {
                     self.pos += 1;
                     break;
                 }
                 Token::Comma => {
                     self.pos += 1;
+                    // 宽松模式下允许逗号后紧跟闭合括号（尾逗号）
+                    if self.lenient
+                        && self.pos < self.tokens.len()
+                        && self.tokens[self.pos].0 == Token::RightBracket
+                    {
+                        self.pos += 1;
+                        break;
+                    }
                     continue;
                 }
-                _ => return Err("Expected comma or closing bracket".to_string()),
+                _ => return Err(self.error_at("Expected comma or closing bracket")),
             }
         }
         Ok(JsonValue::Array(elements))
     }
 }
 
+/// 当前正在解析的容器类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ContainerKind {
+    Object,
+    Array,
+}
+
+/// 容器内当前期望的是键还是值（对象进入时为 ExpectKey，遇到 `:` 后切换为 ExpectValue）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParseState {
+    ExpectKey,
+    ExpectValue,
+}
+
+const VALUE_STARTS: [TokenKind; 7] = [
+    TokenKind::String,
+    TokenKind::Number,
+    TokenKind::True,
+    TokenKind::False,
+    TokenKind::Null,
+    TokenKind::LeftBrace,
+    TokenKind::LeftBracket,
+];
+
+/// 容器顶层闭合或某个值结束后，下一个允许出现的 token
+fn after_value(container_stack: &[ContainerKind]) -> Vec<TokenKind> {
+    match container_stack.last() {
+        Some(ContainerKind::Object) => vec![TokenKind::Comma, TokenKind::RightBrace],
+        Some(ContainerKind::Array) => vec![TokenKind::Comma, TokenKind::RightBracket],
+        None => vec![TokenKind::Eof],
+    }
+}
+
+/// 基于显式的容器栈/状态栈校验 token 流，而不是递归下降；
+/// 每一步都维护一个 `next_allowed` 集合，使错误能准确说出"期望什么，实际是什么"
+fn validate_with_stack(
+    tokens: &[(Token, usize, usize)],
+    eof_line: usize,
+    eof_col: usize,
+    lenient: bool,
+) -> Result<(), String> {
+    let mut container_stack: Vec<ContainerKind> = Vec::new();
+    let mut state_stack: Vec<ParseState> = Vec::new();
+    let mut next_allowed: Vec<TokenKind> = VALUE_STARTS.to_vec();
+    let mut consumed_root = false;
+
+    for (token, line, col) in tokens {
+        let kind = token.kind();
+        if !next_allowed.contains(&kind) {
+            return Err(format!(
+                "expected one of {}, found {:?} at line {line}, column {col}",
+                format_kinds(&next_allowed),
+                kind.label()
+            ));
+        }
+
+        match kind {
+            TokenKind::LeftBrace => {
+                container_stack.push(ContainerKind::Object);
+                state_stack.push(ParseState::ExpectKey);
+                next_allowed = vec![TokenKind::String, TokenKind::RightBrace];
+            }
+            TokenKind::LeftBracket => {
+                container_stack.push(ContainerKind::Array);
+                state_stack.push(ParseState::ExpectValue);
+                next_allowed = VALUE_STARTS.to_vec();
+                next_allowed.push(TokenKind::RightBracket);
+            }
+            TokenKind::RightBrace | TokenKind::RightBracket => {
+                container_stack.pop();
+                state_stack.pop();
+                next_allowed = after_value(&container_stack);
+                consumed_root = container_stack.is_empty();
+            }
+            TokenKind::String if matches!(state_stack.last(), Some(ParseState::ExpectKey)) => {
+                next_allowed = vec![TokenKind::Colon];
+            }
+            TokenKind::Colon => {
+                *state_stack.last_mut().unwrap() = ParseState::ExpectValue;
+                next_allowed = VALUE_STARTS.to_vec();
+            }
+            TokenKind::Comma => match container_stack.last() {
+                Some(ContainerKind::Object) => {
+                    *state_stack.last_mut().unwrap() = ParseState::ExpectKey;
+                    next_allowed = vec![TokenKind::String];
+                    // 宽松模式下允许逗号后紧跟 `}`（尾逗号）
+                    if lenient {
+                        next_allowed.push(TokenKind::RightBrace);
+                    }
+                }
+                Some(ContainerKind::Array) => {
+                    next_allowed = VALUE_STARTS.to_vec();
+                    // 宽松模式下允许逗号后紧跟 `]`（尾逗号）
+                    if lenient {
+                        next_allowed.push(TokenKind::RightBracket);
+                    }
+                }
+                None => unreachable!("a comma is never in next_allowed outside a container"),
+            },
+            // 剩下的情况都是一个标量值：字符串（作为值）、数字、true、false、null
+            _ => {
+                next_allowed = after_value(&container_stack);
+                consumed_root = container_stack.is_empty();
+            }
+        }
+    }
+
+    if !container_stack.is_empty() {
+        return Err(format!(
+            "expected one of {} before end of input",
+            format_kinds(&next_allowed)
+        ));
+    }
+    if !consumed_root {
+        return Err(format!(
+            "Unexpected end of input at line {eof_line}, column {eof_col}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// JSONPath 路径中的一个片段
+#[derive(Debug, Clone)]
+enum PathSegment {
+    Root,
+    Member(String),
+    Index(usize),
+    Wildcard,
+    RecursiveDescent(String),
+}
+
+/// 将 JSONPath 风格的字符串拆分为片段列表，例如 `$.store.book[0].title`、`$..price`、`$.items[*].name`
+fn tokenize_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let chars: Vec<char> = path.chars().collect();
+
+    if chars.first() != Some(&'$') {
+        return Err(format!("JSONPath must start with '$': {path}"));
+    }
+
+    let mut segments = vec![PathSegment::Root];
+    let mut i = 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                i += 1;
+                if chars.get(i) == Some(&'.') {
+                    i += 1;
+                    let name = read_path_name(&chars, &mut i)?;
+                    segments.push(PathSegment::RecursiveDescent(name));
+                } else if chars.get(i) == Some(&'*') {
+                    i += 1;
+                    segments.push(PathSegment::Wildcard);
+                } else {
+                    let name = read_path_name(&chars, &mut i)?;
+                    segments.push(PathSegment::Member(name));
+                }
+            }
+            '[' => {
+                i += 1;
+                if chars.get(i) == Some(&'*') {
+                    i += 1;
+                    expect_char(&chars, &mut i, ']')?;
+                    segments.push(PathSegment::Wildcard);
+                } else if chars.get(i) == Some(&'"') {
+                    i += 1;
+                    let start = i;
+                    while i < chars.len() && chars[i] != '"' {
+                        i += 1;
+                    }
+                    let name: String = chars[start..i].iter().collect();
+                    expect_char(&chars, &mut i, '"')?;
+                    expect_char(&chars, &mut i, ']')?;
+                    segments.push(PathSegment::Member(name));
+                } else {
+                    let start = i;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                    if start == i {
+                        return Err(format!(
+                            "Expected an index or quoted name in '[...]' at position {start}"
+                        ));
+                    }
+                    let index: usize = chars[start..i]
+                        .iter()
+                        .collect::<String>()
+                        .parse()
+                        .map_err(|_| format!("Invalid array index at position {start}"))?;
+                    expect_char(&chars, &mut i, ']')?;
+                    segments.push(PathSegment::Index(index));
+                }
+            }
+            other => {
+                return Err(format!(
+                    "Unexpected character '{other}' in path at position {i}"
+                ))
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+fn read_path_name(chars: &[char], i: &mut usize) -> Result<String, String> {
+    let start = *i;
+    while *i < chars.len() && (chars[*i].is_alphanumeric() || chars[*i] == '_' || chars[*i] == '-')
+    {
+        *i += 1;
+    }
+    if start == *i {
+        return Err(format!("Expected a member name at position {start}"));
+    }
+    Ok(chars[start..*i].iter().collect())
+}
+
+fn expect_char(chars: &[char], i: &mut usize, expected: char) -> Result<(), String> {
+    if chars.get(*i) != Some(&expected) {
+        return Err(format!("Expected '{expected}' at position {i}"));
+    }
+    *i += 1;
+    Ok(())
+}
+
+/// 给定路径片段，在已解析的 `JsonValue` 树中收集匹配项；通配符与递归下降会展开为多个结果
+fn select_path<'a>(segments: &[PathSegment], value: &'a JsonValue) -> Vec<&'a JsonValue> {
+    match segments.split_first() {
+        None => vec![value],
+        Some((PathSegment::Root, rest)) => select_path(rest, value),
+        Some((PathSegment::Member(name), rest)) => match value {
+            JsonValue::Object(pairs) => pairs
+                .iter()
+                .filter(|(k, _)| k == name)
+                .flat_map(|(_, v)| select_path(rest, v))
+                .collect(),
+            _ => Vec::new(),
+        },
+        Some((PathSegment::Index(index), rest)) => match value {
+            JsonValue::Array(items) => items
+                .get(*index)
+                .map(|v| select_path(rest, v))
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        },
+        Some((PathSegment::Wildcard, rest)) => match value {
+            JsonValue::Object(pairs) => pairs
+                .iter()
+                .flat_map(|(_, v)| select_path(rest, v))
+                .collect(),
+            JsonValue::Array(items) => items.iter().flat_map(|v| select_path(rest, v)).collect(),
+            _ => Vec::new(),
+        },
+        Some((PathSegment::RecursiveDescent(name), rest)) => {
+            let mut matches = Vec::new();
+            collect_recursive(value, name, &mut matches);
+            matches
+                .into_iter()
+                .flat_map(|v| select_path(rest, v))
+                .collect()
+        }
+    }
+}
+
+/// 在整棵树中递归收集所有名为 `name` 的对象成员，供 `..name` 使用
+fn collect_recursive<'a>(value: &'a JsonValue, name: &str, out: &mut Vec<&'a JsonValue>) {
+    match value {
+        JsonValue::Object(pairs) => {
+            for (k, v) in pairs {
+                if k == name {
+                    out.push(v);
+                }
+                collect_recursive(v, name, out);
+            }
+        }
+        JsonValue::Array(items) => {
+            for v in items {
+                collect_recursive(v, name, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 每级缩进使用的空格数
+const INDENT_WIDTH: usize = 2;
+
+/// 将字符串字面量转义后写入 `out`（`read_string` 转义解码的逆操作）
+fn encode_string(s: &str, out: &mut String) {
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{0008}' => out.push_str("\\b"),
+            '\u{000C}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// 将 `JsonValue` 重新序列化为 JSON 文本；`pretty` 为真时按 `indent` 所处的深度换行并缩进
+fn encode(value: &JsonValue, out: &mut String, indent: usize, pretty: bool) {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        JsonValue::Number(n) => out.push_str(&n.to_string()),
+        JsonValue::String(s) => encode_string(s, out),
+        JsonValue::Array(items) => {
+            if items.is_empty() {
+                out.push_str("[]");
+                return;
+            }
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if pretty {
+                    out.push('\n');
+                    out.push_str(&" ".repeat((indent + 1) * INDENT_WIDTH));
+                }
+                encode(item, out, indent + 1, pretty);
+                if i + 1 < items.len() {
+                    out.push(',');
+                }
+            }
+            if pretty {
+                out.push('\n');
+                out.push_str(&" ".repeat(indent * INDENT_WIDTH));
+            }
+            out.push(']');
+        }
+        JsonValue::Object(pairs) => {
+            if pairs.is_empty() {
+                out.push_str("{}");
+                return;
+            }
+            out.push('{');
+            for (i, (key, value)) in pairs.iter().enumerate() {
+                if pretty {
+                    out.push('\n');
+                    out.push_str(&" ".repeat((indent + 1) * INDENT_WIDTH));
+                }
+                encode_string(key, out);
+                out.push(':');
+                if pretty {
+                    out.push(' ');
+                }
+                encode(value, out, indent + 1, pretty);
+                if i + 1 < pairs.len() {
+                    out.push(',');
+                }
+            }
+            if pretty {
+                out.push('\n');
+                out.push_str(&" ".repeat(indent * INDENT_WIDTH));
+            }
+            out.push('}');
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum OutputFormat {
+    Compact,
+    Pretty,
+}
+
 #[derive(ClapParser, Debug)]
 struct Cli {
     #[arg(short, long)]
     file: Option<PathBuf>,
-}
 
-fn main() -> io::Result<()> {
-    let cli = Cli::parse();
+    /// 使用基于显式栈的校验模式，而不是递归下降解析
+    #[arg(long)]
+    stack_validate: bool,
 
-    if cli.file.is_none() {
-        eprintln!("file is not provided.");
-        std::process::exit(1);
-    }
+    /// 在解析出的 JSON 上求值一个 JSONPath 风格的表达式，并打印匹配到的值
+    #[arg(long)]
+    query: Option<String>,
+
+    /// 重新序列化解析出的 JSON 并打印，而不是打印调试形式
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// 接受 JSON5 风格的方言：`//`、`/* */` 注释以及 `}`/`]` 前的尾逗号
+    #[arg(long)]
+    lenient: bool,
+}
 
-    let json_content = fs::read_to_string(cli.file.unwrap())?;
-    let mut lexer = Lexer::new(json_content);
+/// 对读取到的 JSON 文本进行词法/语法分析，并按 `cli` 选择的模式输出结果
+fn validate(input: String, cli: &Cli) -> io::Result<()> {
+    let mut lexer = Lexer::new(input, cli.lenient);
     let mut tokens = Vec::new();
-    loop {
-        let token = lexer.next_token();
+    let (eof_line, eof_col) = loop {
+        let (token, line, col) = match lexer.next_token() {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("Invalid JSON: {:?}", e);
+                std::process::exit(1);
+            }
+        };
         if token == Token::Eof {
-            break;
+            break (line, col);
         }
         if token != Token::Whitespace {
-            tokens.push(token);
+            tokens.push((token, line, col));
         }
-    }
-    let mut parser = Parser::new(tokens);
+    };
+    if let Some(path) = &cli.query {
+        let mut parser = Parser::new(tokens, eof_line, eof_col, cli.lenient);
+        let json = match parser.parse_document() {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("Invalid JSON: {:?}", e);
+                std::process::exit(1);
+            }
+        };
 
-    match parser.parse() {
-        Ok(json) => {
-            println!("Valid JSON: {:?}", json);
-            std::process::exit(0);
+        match tokenize_path(path) {
+            Ok(segments) => {
+                for m in select_path(&segments, &json) {
+                    println!("{:?}", m);
+                }
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Invalid JSONPath: {}", e);
+                std::process::exit(1);
+            }
         }
-        Err(e) => {
-            eprintln!("Invalid JSON: {:?}", e);
-            std::process::exit(1)
+    } else if let Some(format) = &cli.format {
+        let mut parser = Parser::new(tokens, eof_line, eof_col, cli.lenient);
+        match parser.parse_document() {
+            Ok(json) => {
+                let pretty = matches!(format, OutputFormat::Pretty);
+                let mut out = String::new();
+                encode(&json, &mut out, 0, pretty);
+                println!("{out}");
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Invalid JSON: {:?}", e);
+                std::process::exit(1);
+            }
         }
+    } else if cli.stack_validate {
+        match validate_with_stack(&tokens, eof_line, eof_col, cli.lenient) {
+            Ok(()) => {
+                println!("Valid JSON");
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Invalid JSON: {}", e);
+                std::process::exit(1)
+            }
+        }
+    } else {
+        let mut parser = Parser::new(tokens, eof_line, eof_col, cli.lenient);
+
+        match parser.parse_document() {
+            Ok(json) => {
+                println!("Valid JSON: {:?}", json);
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Invalid JSON: {:?}", e);
+                std::process::exit(1)
+            }
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let cli = Cli::parse();
+    // 如果这里是在终端运行则返回 true，如果是通过管道运行则返回 false
+    let is_tty = atty::is(atty::Stream::Stdin);
+
+    if let Some(file) = &cli.file {
+        let json_content = fs::read_to_string(file)?;
+        validate(json_content, &cli)
+    } else if !is_tty {
+        let mut json_content = String::new();
+        io::stdin().read_to_string(&mut json_content)?;
+        validate(json_content, &cli)
+    } else {
+        eprintln!("file is not provided.");
+        std::process::exit(1);
     }
 }